@@ -16,9 +16,13 @@ use middle::typeck;
 use util::ppaux;
 
 use std::cell::RefCell;
+use std::hashmap::HashSet;
+use std::{i8, i16, i32, i64, u8, u16, u32, u64};
 use syntax::ast::*;
+use syntax::attr;
 use syntax::codemap;
 use syntax::{ast_util, ast_map};
+use syntax::parse::token;
 use syntax::visit::Visitor;
 use syntax::visit;
 
@@ -28,6 +32,28 @@ struct CheckCrateVisitor {
     def_map: resolve::DefMap,
     method_map: typeck::method_map,
     tcx: ty::ctxt,
+    // Stack of (param binding id, actual argument expression) pairs active
+    // while re-checking the body of a `#[const_fn]` call, innermost call
+    // last. Lets `ExprPath`/constant folding treat a reference to one of the
+    // function's own parameters as a reference to the value it was called
+    // with.
+    const_fn_args: @RefCell<~[(NodeId, @Expr)]>,
+    // Stack of the `ExprCall` node ids of `#[const_fn]` calls currently being
+    // re-checked, innermost call last (see `check_const_fn_call`). A
+    // `#[const_fn]`'s body is the same AST node re-entered once per call
+    // site with different substituted arguments, so folding its tail
+    // expression must be scoped to the call it's being folded for; this is
+    // what makes `folded_consts` below safe to key per call.
+    const_fn_call_ids: @RefCell<~[NodeId]>,
+    // Ids of `ExprBinary`/`ExprUnary` nodes whose constant value has already
+    // been folded (and, if bad, reported) as part of folding some ancestor
+    // expression, keyed by (enclosing call id or DUMMY_NODE_ID, node id).
+    // `visit::walk_expr` will still visit these nodes on its own afterwards;
+    // this set lets `check_const_arith` recognize that and avoid emitting
+    // the same overflow/div-by-zero error twice. Scoping the key on the
+    // enclosing call keeps two different calls to the same `#[const_fn]`
+    // (hence two visits of the same body node id) from being conflated.
+    folded_consts: @RefCell<HashSet<(NodeId, NodeId)>>,
 }
 
 impl Visitor<bool> for CheckCrateVisitor {
@@ -38,8 +64,8 @@ impl Visitor<bool> for CheckCrateVisitor {
         check_pat(self, p, env);
     }
     fn visit_expr(&mut self, ex:@Expr, env:bool) {
-        check_expr(self, self.sess, self.def_map, self.method_map,
-                   self.tcx, ex, env);
+        check_expr(self, self.sess, self.ast_map, self.def_map,
+                   self.method_map, self.tcx, ex, env);
     }
 }
 
@@ -55,6 +81,9 @@ pub fn check_crate(sess: Session,
         def_map: def_map,
         method_map: method_map,
         tcx: tcx,
+        const_fn_args: @RefCell::new(~[]),
+        const_fn_call_ids: @RefCell::new(~[]),
+        folded_consts: @RefCell::new(HashSet::new()),
     };
     visit::walk_crate(&mut v, crate, false);
     sess.abort_if_errors();
@@ -68,10 +97,14 @@ pub fn check_item(v: &mut CheckCrateVisitor,
                   _is_const: bool) {
     match it.node {
       item_static(_, _, ex) => {
-        v.visit_expr(ex, true);
+        // Check for cycles (including through `#[const_fn]` calls) before
+        // the semantic pass below, which itself recurses into the bodies
+        // of `#[const_fn]`s called and would otherwise loop forever on one.
         check_item_recursion(sess, ast_map, def_map, it);
+        v.visit_expr(ex, true);
       }
       item_enum(ref enum_definition, _) => {
+        check_item_recursion(sess, ast_map, def_map, it);
         for var in (*enum_definition).variants.iter() {
             for ex in var.node.disr_expr.iter() {
                 v.visit_expr(*ex, true);
@@ -108,6 +141,7 @@ pub fn check_pat(v: &mut CheckCrateVisitor, p: &Pat, _is_const: bool) {
 
 pub fn check_expr(v: &mut CheckCrateVisitor,
                   sess: Session,
+                  ast_map: ast_map::map,
                   def_map: resolve::DefMap,
                   method_map: typeck::method_map,
                   tcx: ty::ctxt,
@@ -123,11 +157,14 @@ pub fn check_expr(v: &mut CheckCrateVisitor,
           }
           ExprLit(@codemap::Spanned {node: lit_str(..), ..}) => { }
           ExprBinary(..) | ExprUnary(..) => {
-            let method_map = method_map.borrow();
-            if method_map.get().contains_key(&e.id) {
-                sess.span_err(e.span, "user-defined operators are not \
-                                       allowed in constant expressions");
+            {
+                let method_map = method_map.borrow();
+                if method_map.get().contains_key(&e.id) {
+                    sess.span_err(e.span, "user-defined operators are not \
+                                           allowed in constant expressions");
+                }
             }
+            check_const_arith(v, sess, def_map, tcx, e);
           }
           ExprLit(_) => (),
           ExprCast(_, _) => {
@@ -148,14 +185,22 @@ pub fn check_expr(v: &mut CheckCrateVisitor,
                     e.span, "paths in constants may only refer to \
                              items without type parameters");
             }
-            let def_map = def_map.borrow();
-            match def_map.get().find(&e.id) {
-              Some(&DefStatic(..)) |
-              Some(&DefFn(_, _)) |
-              Some(&DefVariant(_, _, _)) |
-              Some(&DefStruct(_)) => { }
-
-              Some(&def) => {
+            let opt_def = {
+                let def_map = def_map.borrow();
+                def_map.get().find(&e.id).map(|d| *d)
+            };
+            match opt_def {
+              Some(DefStatic(..)) |
+              Some(DefFn(_, _)) |
+              Some(DefVariant(_, _, _)) |
+              Some(DefStruct(_)) => { }
+
+              // A reference to a `#[const_fn]`'s own parameter, while its
+              // body is being re-checked for a particular call: treat it as
+              // a reference to the (already-checked) argument it stands for.
+              Some(DefArg(..)) if const_fn_arg_value(v, def_map, e).is_some() => { }
+
+              Some(def) => {
                 debug!("(checking const) found bad def: {:?}", def);
                 sess.span_err(
                     e.span,
@@ -167,20 +212,29 @@ pub fn check_expr(v: &mut CheckCrateVisitor,
               }
             }
           }
-          ExprCall(callee, _, NoSugar) => {
-            let def_map = def_map.borrow();
-            match def_map.get().find(&callee.id) {
-                Some(&DefStruct(..)) => {}    // OK.
-                Some(&DefVariant(..)) => {}    // OK.
+          ExprCall(callee, ref args, NoSugar) => {
+            let opt_def = {
+                let def_map = def_map.borrow();
+                def_map.get().find(&callee.id).map(|d| *d)
+            };
+            match opt_def {
+                Some(DefStruct(..)) => {}    // OK.
+                Some(DefVariant(..)) => {}    // OK.
+                Some(DefFn(did, _)) if ast_util::is_local(did) &&
+                                        is_const_fn(ast_map, did.node) => {
+                    check_const_fn_call(v, sess, ast_map, def_map, method_map,
+                                         tcx, did.node, e.id, *args);
+                }
                 _ => {
                     sess.span_err(
                         e.span,
                         "function calls in constants are limited to \
-                         struct and enum constructors");
+                         struct and enum constructors, and functions \
+                         marked #[const_fn]");
                 }
             }
           }
-          ExprParen(e) => { check_expr(v, sess, def_map, method_map,
+          ExprParen(e) => { check_expr(v, sess, ast_map, def_map, method_map,
                                         tcx, e, is_const); }
           ExprVstore(_, ExprVstoreSlice) |
           ExprVec(_, MutImmutable) |
@@ -211,6 +265,409 @@ pub fn check_expr(v: &mut CheckCrateVisitor,
     visit::walk_expr(v, e, is_const);
 }
 
+// A folded constant value, kept in whichever of Rust's two machine
+// representations actually spans its type's full range: `ConstInt` for the
+// signed integer types, `ConstUint` for the unsigned ones. Mixing the two up
+// (e.g. by always widening into `i64`) would silently corrupt `u64`/`uint`
+// values above `i64::MAX`.
+enum ConstVal {
+    ConstInt(i64),
+    ConstUint(u64),
+}
+
+// Best-effort constant folding for `ExprBinary`, used to catch arithmetic
+// overflow and division/remainder by zero at const-check time. Only a
+// handful of node kinds are understood; anything else (a path to another
+// static, a method call, ...) makes folding bail out with `None` rather
+// than risk rejecting a constant that is actually fine.
+fn eval_const_expr(v: &mut CheckCrateVisitor, sess: Session,
+                   def_map: resolve::DefMap, tcx: ty::ctxt,
+                   e: @Expr) -> Option<ConstVal> {
+    match e.node {
+        ExprParen(sub) => eval_const_expr(v, sess, def_map, tcx, sub),
+        ExprLit(lit) => eval_const_lit(tcx, e, *lit),
+        // A reference to a `#[const_fn]` parameter folds to whatever the
+        // actual argument at this call site folds to.
+        ExprPath(..) => {
+            match const_fn_arg_value(v, def_map, e) {
+                Some(actual) => eval_const_expr(v, sess, def_map, tcx, actual),
+                None => None
+            }
+        }
+        ExprUnary(_, UnNeg, inner) => {
+            mark_folded(v, e);
+            match eval_const_expr(v, sess, def_map, tcx, inner) {
+                Some(ConstInt(i)) => Some(ConstInt(-i)),
+                _ => None
+            }
+        }
+        ExprBinary(_, op, a, b) => {
+            mark_folded(v, e);
+            match (eval_const_expr(v, sess, def_map, tcx, a),
+                   eval_const_expr(v, sess, def_map, tcx, b)) {
+                (Some(ConstInt(x)), Some(ConstInt(y))) => {
+                    eval_int_binop(sess, tcx, e, op, x, y).map(|r| ConstInt(r))
+                }
+                (Some(ConstUint(x)), Some(ConstUint(y))) => {
+                    eval_uint_binop(sess, tcx, e, op, x, y).map(|r| ConstUint(r))
+                }
+                _ => None
+            }
+        }
+        _ => None
+    }
+}
+
+fn eval_const_lit(tcx: ty::ctxt, e: @Expr,
+                  lit: @codemap::Spanned<lit_>) -> Option<ConstVal> {
+    match lit.node {
+        lit_int(i, _) => Some(ConstInt(i)),
+        // An unsuffixed literal takes its type from context, so pick the
+        // representation from that type rather than from the literal.
+        lit_int_unsuffixed(i) => {
+            if uint_ty_max(tcx, e).is_some() {
+                Some(ConstUint(i as u64))
+            } else {
+                Some(ConstInt(i))
+            }
+        }
+        lit_uint(u, _) => Some(ConstUint(u)),
+        _ => None
+    }
+}
+
+// The number of bits in the integral type of `e`, or `None` if `e` doesn't
+// have one (e.g. it's a float or a pointer). Used to keep shift counts in
+// bounds: a native `<<`/`>>` with an out-of-range count is UB, and a count
+// that merely exceeds the *result* type's width (legal, since the right
+// operand of a shift need not match the left operand's type) must not be
+// folded at all.
+fn int_ty_bits(tcx: ty::ctxt, e: @Expr) -> Option<uint> {
+    match ty::get(ty::expr_ty(tcx, e)).sty {
+        ty::ty_int(ty_i) | ty::ty_int(ty_i64) => Some(64),
+        ty::ty_int(ty_i8) => Some(8),
+        ty::ty_int(ty_i16) => Some(16),
+        ty::ty_int(ty_i32) => Some(32),
+        ty::ty_uint(ty_u) | ty::ty_uint(ty_u64) => Some(64),
+        ty::ty_uint(ty_u8) => Some(8),
+        ty::ty_uint(ty_u16) => Some(16),
+        ty::ty_uint(ty_u32) => Some(32),
+        _ => None
+    }
+}
+
+// The `[min, max]` range representable by the type of `e`, for the signed
+// integer types only; `None` for everything else (including the unsigned
+// types, which `eval_uint_binop` range-checks separately via `uint_ty_max`).
+fn int_ty_range(tcx: ty::ctxt, e: @Expr) -> Option<(i64, i64)> {
+    match ty::get(ty::expr_ty(tcx, e)).sty {
+        ty::ty_int(ty_i) | ty::ty_int(ty_i64) => Some((i64::MIN, i64::MAX)),
+        ty::ty_int(ty_i8) => Some((i8::MIN as i64, i8::MAX as i64)),
+        ty::ty_int(ty_i16) => Some((i16::MIN as i64, i16::MAX as i64)),
+        ty::ty_int(ty_i32) => Some((i32::MIN as i64, i32::MAX as i64)),
+        _ => None
+    }
+}
+
+// The maximum value representable by the type of `e`, for the unsigned
+// integer types only (the minimum is always zero); `None` otherwise.
+fn uint_ty_max(tcx: ty::ctxt, e: @Expr) -> Option<u64> {
+    match ty::get(ty::expr_ty(tcx, e)).sty {
+        ty::ty_uint(ty_u) | ty::ty_uint(ty_u64) => Some(u64::MAX),
+        ty::ty_uint(ty_u8) => Some(u8::MAX as u64),
+        ty::ty_uint(ty_u16) => Some(u16::MAX as u64),
+        ty::ty_uint(ty_u32) => Some(u32::MAX as u64),
+        _ => None
+    }
+}
+
+fn eval_int_binop(sess: Session, tcx: ty::ctxt, e: @Expr, op: BinOp,
+                  x: i64, y: i64) -> Option<i64> {
+    let result = match op {
+        BiDiv | BiRem => {
+            if y == 0 {
+                sess.span_err(e.span,
+                              "attempted to divide by zero in a \
+                               constant expression");
+                return None;
+            }
+            // i64::MIN / -1 (and the same for `%`) traps on the underlying
+            // hardware division instruction; this would abort the compiler
+            // rather than reporting a clean error, so it must be caught
+            // before the native `/`/`%` ever runs.
+            if x == i64::MIN && y == -1 {
+                sess.span_err(e.span,
+                              "constant arithmetic overflows the type of \
+                               this expression");
+                return None;
+            }
+            if op == BiDiv { x / y } else { x % y }
+        }
+        BiAdd => {
+            if (y > 0 && x > i64::MAX - y) || (y < 0 && x < i64::MIN - y) {
+                sess.span_err(e.span,
+                              "constant arithmetic overflows the type of \
+                               this expression");
+                return None;
+            }
+            x + y
+        }
+        BiSub => {
+            if (y < 0 && x > i64::MAX + y) || (y > 0 && x < i64::MIN + y) {
+                sess.span_err(e.span,
+                              "constant arithmetic overflows the type of \
+                               this expression");
+                return None;
+            }
+            x - y
+        }
+        BiMul => {
+            // Multiplication can't trap the way `i64::MIN / -1` does, but
+            // checking the result's own overflow requires dividing back out
+            // by `x`, which would trap on exactly that same pair of values;
+            // rule it out up front.
+            if (x == -1 && y == i64::MIN) || (y == -1 && x == i64::MIN) {
+                sess.span_err(e.span,
+                              "constant arithmetic overflows the type of \
+                               this expression");
+                return None;
+            }
+            let result = x * y;
+            if x != 0 && result / x != y {
+                sess.span_err(e.span,
+                              "constant arithmetic overflows the type of \
+                               this expression");
+                return None;
+            }
+            result
+        }
+        BiShl => {
+            match int_ty_bits(tcx, e) {
+                Some(bits) if y >= 0 && (y as u64) < bits as u64 => x << y,
+                // An out-of-range shift count is legal at the type level
+                // (the rhs of `<<` need not fit the lhs's type) but its
+                // value is not something we can safely fold.
+                _ => return None
+            }
+        }
+        _ => return None
+    };
+    match int_ty_range(tcx, e) {
+        Some((min, max)) if result < min || result > max => {
+            sess.span_err(e.span,
+                          "constant arithmetic overflows the type of \
+                           this expression");
+            None
+        }
+        _ => Some(result)
+    }
+}
+
+fn eval_uint_binop(sess: Session, tcx: ty::ctxt, e: @Expr, op: BinOp,
+                   x: u64, y: u64) -> Option<u64> {
+    let result = match op {
+        BiDiv | BiRem => {
+            if y == 0 {
+                sess.span_err(e.span,
+                              "attempted to divide by zero in a \
+                               constant expression");
+                return None;
+            }
+            if op == BiDiv { x / y } else { x % y }
+        }
+        BiAdd => {
+            if x > u64::MAX - y {
+                sess.span_err(e.span,
+                              "constant arithmetic overflows the type of \
+                               this expression");
+                return None;
+            }
+            x + y
+        }
+        BiSub => {
+            if y > x {
+                sess.span_err(e.span,
+                              "constant arithmetic overflows the type of \
+                               this expression");
+                return None;
+            }
+            x - y
+        }
+        BiMul => {
+            if y != 0 && x > u64::MAX / y {
+                sess.span_err(e.span,
+                              "constant arithmetic overflows the type of \
+                               this expression");
+                return None;
+            }
+            x * y
+        }
+        BiShl => {
+            match int_ty_bits(tcx, e) {
+                Some(bits) if (y as u64) < bits as u64 => x << y,
+                _ => return None
+            }
+        }
+        _ => return None
+    };
+    match uint_ty_max(tcx, e) {
+        Some(max) if result > max => {
+            sess.span_err(e.span,
+                          "constant arithmetic overflows the type of \
+                           this expression");
+            None
+        }
+        _ => Some(result)
+    }
+}
+
+// The id that scopes `folded_consts` entries created right now: the
+// innermost `#[const_fn]` call currently being re-checked, or
+// `DUMMY_NODE_ID` when folding an ordinary constant expression that isn't
+// inside any call.
+fn current_fold_scope(v: &CheckCrateVisitor) -> NodeId {
+    let stack = v.const_fn_call_ids.borrow();
+    stack.get().last().map(|&id| id).unwrap_or(DUMMY_NODE_ID)
+}
+
+fn mark_folded(v: &mut CheckCrateVisitor, e: @Expr) {
+    let scope = current_fold_scope(v);
+    let mut folded = v.folded_consts.borrow_mut();
+    folded.get().insert((scope, e.id));
+}
+
+// `visit::walk_expr` visits every node of a constant expression, including
+// ones already folded as part of an ancestor `ExprBinary`/`ExprUnary` (see
+// `eval_const_expr`). Only fold (and potentially report an error for) a
+// given (call scope, node) pair once, the first time it's reached.
+fn check_const_arith(v: &mut CheckCrateVisitor, sess: Session,
+                     def_map: resolve::DefMap, tcx: ty::ctxt, e: @Expr) {
+    let scope = current_fold_scope(v);
+    {
+        let folded = v.folded_consts.borrow();
+        if folded.get().contains(&(scope, e.id)) {
+            return;
+        }
+    }
+    match e.node {
+        ExprBinary(..) | ExprUnary(..) => { eval_const_expr(v, sess, def_map, tcx, e); }
+        _ => ()
+    }
+}
+
+// Is `def_id` the id of a function item tagged `#[const_fn]`?
+fn is_const_fn(ast_map: ast_map::map, def_id: NodeId) -> bool {
+    let ast_map = ast_map.borrow();
+    match ast_map.get().find(&def_id) {
+        Some(&ast_map::node_item(it, _)) => {
+            attr::contains_name(it.attrs, "const_fn")
+        }
+        _ => false
+    }
+}
+
+// Check a call to a `#[const_fn]` from within a constant expression: bind
+// its parameters to the actual argument expressions and re-check its body
+// as though it were the constant expression itself. Cycles through a chain
+// of `#[const_fn]` calls are caught up front by `check_item_recursion`
+// (see `check_item`), so no recursion guard is needed here. `call_id` (the
+// id of the `ExprCall` itself) scopes the body's `folded_consts` entries to
+// this particular call, since the same body node id is re-checked once per
+// call site with different arguments substituted in.
+fn check_const_fn_call(v: &mut CheckCrateVisitor,
+                       sess: Session,
+                       ast_map: ast_map::map,
+                       def_map: resolve::DefMap,
+                       method_map: typeck::method_map,
+                       tcx: ty::ctxt,
+                       fn_id: NodeId,
+                       call_id: NodeId,
+                       args: &[@Expr]) {
+    let fn_item = {
+        let ast_map = ast_map.borrow();
+        match ast_map.get().get_copy(&fn_id) {
+            ast_map::node_item(it, _) => it,
+            _ => sess.bug("#[const_fn] call does not resolve to an item")
+        }
+    };
+    match fn_item.node {
+        item_fn(ref decl, _, _, _, ref body) => {
+            if !body.stmts.is_empty() {
+                sess.span_err(
+                    fn_item.span,
+                    "#[const_fn] functions must consist of a single \
+                     tail expression");
+                return;
+            }
+            match body.expr {
+                Some(tail_expr) => {
+                    // Bind each by-value parameter to the expression it was
+                    // called with, so that a reference to it inside the
+                    // body is treated as a reference to that expression
+                    // (already required to be constant at the call site)
+                    // rather than rejected as a local/argument def.
+                    let bindings: ~[(NodeId, @Expr)] =
+                        decl.inputs.iter().zip(args.iter())
+                            .filter_map(|(arg, actual)| {
+                                match arg.pat.node {
+                                    PatIdent(..) => Some((arg.pat.id, *actual)),
+                                    _ => None
+                                }
+                            })
+                            .collect();
+                    let base_len = {
+                        let stack = v.const_fn_args.borrow();
+                        stack.get().len()
+                    };
+                    {
+                        let mut stack = v.const_fn_args.borrow_mut();
+                        stack.get().push_all_move(bindings);
+                    }
+                    {
+                        let mut call_ids = v.const_fn_call_ids.borrow_mut();
+                        call_ids.get().push(call_id);
+                    }
+                    check_expr(v, sess, ast_map, def_map, method_map,
+                               tcx, tail_expr, true);
+                    {
+                        let mut call_ids = v.const_fn_call_ids.borrow_mut();
+                        call_ids.get().pop();
+                    }
+                    {
+                        let mut stack = v.const_fn_args.borrow_mut();
+                        stack.get().truncate(base_len);
+                    }
+                }
+                None => {
+                    sess.span_err(
+                        fn_item.span,
+                        "#[const_fn] functions must consist of a single \
+                         tail expression");
+                }
+            }
+        }
+        _ => sess.span_bug(fn_item.span, "#[const_fn] on a non-fn item?!")
+    }
+}
+
+// If `e` is a reference to the binding of a `#[const_fn]` parameter that is
+// currently being substituted (see `check_const_fn_call`), the actual
+// argument expression it was called with; otherwise `None`.
+fn const_fn_arg_value(v: &CheckCrateVisitor, def_map: resolve::DefMap,
+                      e: @Expr) -> Option<@Expr> {
+    let def_id = {
+        let def_map = def_map.borrow();
+        match def_map.get().find(&e.id) {
+            Some(&DefArg(id, _)) => id,
+            _ => return None
+        }
+    };
+    let stack = v.const_fn_args.borrow();
+    stack.get().iter().rev()
+        .find(|&&(id, _)| id == def_id)
+        .map(|&(_, actual)| actual)
+}
+
 #[deriving(Clone)]
 struct env {
     root_it: @item,
@@ -244,18 +701,30 @@ pub fn check_item_recursion(sess: Session,
 
 impl Visitor<()> for CheckItemRecursionVisitor {
     fn visit_item(&mut self, it: @item, _: ()) {
-        {
-            let mut idstack = self.env.idstack.borrow_mut();
-            if idstack.get().iter().any(|x| x == &(it.id)) {
-                self.env.sess.span_fatal(self.env.root_it.span,
-                                         "recursive constant");
+        let opt_pos = {
+            let idstack = self.env.idstack.borrow();
+            idstack.get().iter().position(|x| x == &(it.id))
+        };
+        match opt_pos {
+            Some(pos) => {
+                let mut cycle = {
+                    let idstack = self.env.idstack.borrow();
+                    idstack.get().slice_from(pos).to_owned()
+                };
+                cycle.push(it.id);
+                self.report_cycle(cycle);
+            }
+            None => {
+                {
+                    let mut idstack = self.env.idstack.borrow_mut();
+                    idstack.get().push(it.id);
+                }
+                visit::walk_item(self, it, ());
+                {
+                    let mut idstack = self.env.idstack.borrow_mut();
+                    idstack.get().pop();
+                }
             }
-            idstack.get().push(it.id);
-        }
-        visit::walk_item(self, it, ());
-        {
-            let mut idstack = self.env.idstack.borrow_mut();
-            idstack.get().pop();
         }
     }
 
@@ -277,8 +746,55 @@ impl Visitor<()> for CheckItemRecursionVisitor {
                     _ => ()
                 }
             },
+            // A call to a `#[const_fn]` re-checks that function's body in
+            // const position (see `check_const_fn_call`), so it needs the
+            // same cycle guard as a reference to another constant: walk into
+            // the callee here too, rather than only detecting the cycle
+            // once `check_const_fn_call` is already recursing without limit.
+            ExprCall(callee, _, NoSugar) => {
+                let def_map = self.env.def_map.borrow();
+                match def_map.get().find(&callee.id) {
+                    Some(&DefFn(def_id, _)) if
+                            ast_util::is_local(def_id) &&
+                            is_const_fn(self.env.ast_map, def_id.node) => {
+                        let ast_map = self.env.ast_map.borrow();
+                        match ast_map.get().get_copy(&def_id.node) {
+                            ast_map::node_item(it, _) => {
+                                self.visit_item(it, ());
+                            }
+                            _ => fail!("const fn not bound to an item")
+                        }
+                    }
+                    _ => ()
+                }
+            },
             _ => ()
         }
         visit::walk_expr(self, e, ());
     }
 }
+
+impl CheckItemRecursionVisitor {
+    // Report the cycle of constants found in `idstack`, in the order they
+    // were encountered: cycle[0] references cycle[1], and so on, and the
+    // last entry is cycle[0] again, closing the loop.
+    fn report_cycle(&self, cycle: ~[NodeId]) {
+        self.env.sess.span_err(self.env.root_it.span, "recursive constant");
+        let ast_map = self.env.ast_map.borrow();
+        for ids in cycle.windows(2) {
+            let (from_id, to_id) = (ids[0], ids[1]);
+            match (ast_map.get().find(&from_id), ast_map.get().find(&to_id)) {
+                (Some(&ast_map::node_item(from_it, _)),
+                 Some(&ast_map::node_item(to_it, _))) => {
+                    self.env.sess.span_note(
+                        from_it.span,
+                        format!("`{}` refers to `{}`...",
+                                token::get_ident(from_it.ident),
+                                token::get_ident(to_it.ident)));
+                }
+                _ => ()
+            }
+        }
+        self.env.sess.abort_if_errors();
+    }
+}